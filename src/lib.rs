@@ -1,7 +1,13 @@
-use std::{collections::HashMap, error::Error, fmt::Display, process::Command};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    io::{self, Write},
+    process::{Command, ExitStatus, Stdio},
+    string::FromUtf8Error,
+    thread,
+};
 
-/// **DISCLAIMER! This crate uses the shell to pipe strings into dmenu.**
-///
 /// The dmenu wrapper.
 /// This struct is built using a builder pattern and finally executed.
 /// The items must implement Display to be displayed by dmenu.
@@ -16,13 +22,15 @@ use std::{collections::HashMap, error::Error, fmt::Display, process::Command};
 ///                    .execute(&items);
 /// //Prints selected item to stdout
 /// if let Ok(item) = chosen {
-///     println!("{}", chosen);
+///     println!("{}", item);
 /// }
 /// ```
 #[derive(Clone, Debug, PartialOrd, PartialEq, Ord, Eq)]
 pub struct DMenu<'a> {
+    backend: Backend,
     on_top: bool,
     case_insensitive: bool,
+    password: bool,
     vertical_lines: Option<i32>,
     monitor: Option<i32>,
     prompt: Option<&'a str>,
@@ -31,13 +39,16 @@ pub struct DMenu<'a> {
     normal_foreground_color: Option<Color<'a>>,
     selected_background_color: Option<Color<'a>>,
     selected_foreground_color: Option<Color<'a>>,
+    default_item: Option<&'a str>,
 }
 
 impl Default for DMenu<'_> {
     fn default() -> Self {
         Self {
+            backend: Backend::Dmenu,
             on_top: true,
             case_insensitive: false,
+            password: false,
             vertical_lines: None,
             monitor: None,
             prompt: None,
@@ -46,11 +57,55 @@ impl Default for DMenu<'_> {
             normal_foreground_color: None,
             selected_background_color: None,
             selected_foreground_color: None,
+            default_item: None,
+        }
+    }
+}
+
+/// A dmenu-protocol-compatible launcher: a program that reads newline-separated
+/// items on stdin and prints the chosen one(s) to stdout.
+#[derive(Clone, Copy, Debug, PartialOrd, PartialEq, Ord, Eq)]
+pub enum Backend {
+    /// The original suckless dmenu.
+    Dmenu,
+    /// rofi run in `-dmenu` mode.
+    Rofi,
+    /// wofi, a Wayland-native dmenu replacement.
+    Wofi,
+    /// bemenu, a Wayland/X11 dmenu clone.
+    Bemenu,
+    /// fuzzel run in `--dmenu` mode.
+    Fuzzel,
+}
+
+impl Backend {
+    fn program(&self) -> &'static str {
+        match self {
+            Backend::Dmenu => "dmenu",
+            Backend::Rofi => "rofi",
+            Backend::Wofi => "wofi",
+            Backend::Bemenu => "bemenu",
+            Backend::Fuzzel => "fuzzel",
         }
     }
 }
 
 impl<'a> DMenu<'a> {
+    /// Selects which dmenu-protocol launcher to spawn. Defaults to `Backend::Dmenu`.
+    ///
+    /// Builder options are translated into that backend's own flag dialect by
+    /// `to_command`; options a backend can't express are silently omitted.
+    /// # Example
+    /// ```
+    /// use dmenu_facade::*;
+    /// let dmenu = DMenu::default()
+    ///                 .backend(Backend::Rofi);
+    /// ```
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
     /// Display dmenu on the bottom of the screen instead of the top
     /// # Example
     /// ```
@@ -88,6 +143,21 @@ impl<'a> DMenu<'a> {
         self
     }
 
+    /// Masks typed characters, for PIN/passphrase style prompts.
+    ///
+    /// Intended for use with `execute_as_input`; the returned `String` holds
+    /// sensitive data and callers should avoid logging or persisting it.
+    /// # Example
+    /// ```
+    /// use dmenu_facade::*;
+    /// let dmenu = DMenu::default()
+    ///                 .password();
+    /// ```
+    pub fn password(mut self) -> Self {
+        self.password = true;
+        self
+    }
+
     /// Display on a specific monitor. Index starts with 0
     /// # Example
     /// ```
@@ -157,50 +227,232 @@ impl<'a> DMenu<'a> {
         self
     }
 
-    /// Formats the dmenu shell string
-    fn to_command(&self) -> String {
-        let mut command = "dmenu".to_string();
+    /// Pre-fills the input buffer with `item`, so pressing Enter immediately
+    /// accepts it while typing still narrows the list as usual.
+    ///
+    /// Backends with a native pre-select/initial-text flag use it directly;
+    /// others fall back to reordering the piped item list so `item` appears
+    /// first, since dmenu-protocol launchers highlight the first entry.
+    /// # Example
+    /// ```
+    /// use dmenu_facade::*;
+    /// let dmenu = DMenu::default()
+    ///                 .with_prompt("Select an item:")
+    ///                 .default_item("World");
+    /// ```
+    pub fn default_item(mut self, item: &'a str) -> Self {
+        self.default_item = Some(item);
+        self
+    }
+
+    /// Whether the configured backend has a native pre-select/initial-text
+    /// flag for `default_item`, as opposed to needing the list reordered.
+    fn supports_native_default_item(&self) -> bool {
+        matches!(self.backend, Backend::Rofi)
+    }
+
+    /// Moves the key for `default_item` to the front of `keys` when the
+    /// backend has no native way to pre-select it, then joins everything
+    /// into the newline-separated list dmenu expects on stdin.
+    fn ordered_list_string(&self, mut keys: Vec<String>) -> String {
+        if let Some(default) = self.default_item {
+            if !self.supports_native_default_item() {
+                let default_key = format!("{}\n", default);
+                if let Some(pos) = keys.iter().position(|key| key == &default_key) {
+                    let key = keys.remove(pos);
+                    keys.insert(0, key);
+                }
+            }
+        }
+        keys.concat()
+    }
+
+    /// Builds the argv tokens the configured backend should be spawned with,
+    /// translated into that backend's own flag dialect.
+    ///
+    /// Each flag and its value is its own token, so prompts, fonts and colors
+    /// containing spaces or shell metacharacters are passed through untouched.
+    /// Options a backend has no equivalent for are silently omitted.
+    fn to_command(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        match self.backend {
+            Backend::Rofi => args.push("-dmenu".to_string()),
+            Backend::Wofi | Backend::Fuzzel => args.push("--dmenu".to_string()),
+            Backend::Dmenu | Backend::Bemenu => {}
+        }
+
         if !self.on_top {
-            command.push_str(" -b");
+            match self.backend {
+                Backend::Dmenu | Backend::Bemenu => args.push("-b".to_string()),
+                Backend::Rofi | Backend::Wofi | Backend::Fuzzel => {} // no bottom-anchor equivalent
+            }
         }
 
         if self.case_insensitive {
-            command.push_str(" -i");
+            match self.backend {
+                Backend::Dmenu | Backend::Bemenu | Backend::Rofi => args.push("-i".to_string()),
+                Backend::Wofi | Backend::Fuzzel => {} // always case-insensitive, or unsupported
+            }
+        }
+
+        if self.password {
+            match self.backend {
+                Backend::Dmenu => args.push("-P".to_string()),
+                Backend::Rofi => args.push("-password".to_string()),
+                Backend::Bemenu => args.push("--password".to_string()),
+                Backend::Wofi | Backend::Fuzzel => {} // no masked-input flag
+            }
         }
 
         if let Some(lines) = self.vertical_lines {
-            command.push_str(&format!(" -l {}", lines))
+            let flag = match self.backend {
+                Backend::Dmenu | Backend::Bemenu => "-l",
+                Backend::Rofi => "-l",
+                Backend::Wofi => "--lines",
+                Backend::Fuzzel => "--lines",
+            };
+            args.push(flag.to_string());
+            args.push(lines.to_string());
         };
 
         if let Some(monitor_index) = self.monitor {
-            command.push_str(&format!(" -m {}", monitor_index));
+            match self.backend {
+                Backend::Dmenu | Backend::Bemenu | Backend::Rofi => {
+                    args.push("-m".to_string());
+                    args.push(monitor_index.to_string());
+                }
+                Backend::Wofi | Backend::Fuzzel => {} // no monitor selection
+            }
         }
 
         if let Some(prompt) = &self.prompt {
-            command.push_str(&format!(" -p '{}'", prompt));
+            let flag = match self.backend {
+                Backend::Dmenu | Backend::Bemenu => "-p",
+                Backend::Rofi => "-p",
+                Backend::Wofi => "--prompt",
+                Backend::Fuzzel => "--prompt",
+            };
+            args.push(flag.to_string());
+            args.push(prompt.to_string());
         }
 
         if let Some(font) = &self.font {
-            command.push_str(&format!(" -fn '{}'", font));
+            match self.backend {
+                Backend::Dmenu => {
+                    args.push("-fn".to_string());
+                    args.push(font.to_string());
+                }
+                Backend::Bemenu => {
+                    args.push("--fn".to_string());
+                    args.push(font.to_string());
+                }
+                Backend::Rofi => {
+                    args.push("-font".to_string());
+                    args.push(font.to_string());
+                }
+                Backend::Wofi | Backend::Fuzzel => {} // styled via CSS/config instead
+            }
         }
 
         if let Some(nb) = &self.normal_background_color {
-            command.push_str(&format!(" -nb '{}'", nb.0));
+            match self.backend {
+                Backend::Dmenu => {
+                    args.push("-nb".to_string());
+                    args.push(nb.0.to_string());
+                }
+                Backend::Bemenu => {
+                    args.push("--nb".to_string());
+                    args.push(nb.0.to_string());
+                }
+                Backend::Rofi | Backend::Wofi | Backend::Fuzzel => {} // styled via theme instead
+            }
         }
 
         if let Some(nf) = &self.normal_foreground_color {
-            command.push_str(&format!(" -nf '{}'", nf.0));
+            match self.backend {
+                Backend::Dmenu => {
+                    args.push("-nf".to_string());
+                    args.push(nf.0.to_string());
+                }
+                Backend::Bemenu => {
+                    args.push("--nf".to_string());
+                    args.push(nf.0.to_string());
+                }
+                Backend::Rofi | Backend::Wofi | Backend::Fuzzel => {}
+            }
         }
 
         if let Some(sb) = &self.selected_background_color {
-            command.push_str(&format!(" -sb '{}'", sb.0));
+            match self.backend {
+                Backend::Dmenu => {
+                    args.push("-sb".to_string());
+                    args.push(sb.0.to_string());
+                }
+                Backend::Bemenu => {
+                    args.push("--sb".to_string());
+                    args.push(sb.0.to_string());
+                }
+                Backend::Rofi | Backend::Wofi | Backend::Fuzzel => {}
+            }
         }
 
         if let Some(sf) = &self.selected_foreground_color {
-            command.push_str(&format!(" -sf '{}'", sf.0));
+            match self.backend {
+                Backend::Dmenu => {
+                    args.push("-sf".to_string());
+                    args.push(sf.0.to_string());
+                }
+                Backend::Bemenu => {
+                    args.push("--sf".to_string());
+                    args.push(sf.0.to_string());
+                }
+                Backend::Rofi | Backend::Wofi | Backend::Fuzzel => {}
+            }
         }
 
-        command
+        if let Some(default) = &self.default_item {
+            if self.backend == Backend::Rofi {
+                args.push("-filter".to_string());
+                args.push(default.to_string());
+            }
+        }
+
+        args
+    }
+
+    /// Spawns the configured backend directly (no shell involved), writes `input`
+    /// to its stdin from a dedicated thread so a large item list can't deadlock
+    /// against a full stdout pipe, and returns whatever it printed to stdout.
+    ///
+    /// The backend is expected to exit with status code 1 when the user
+    /// aborts (e.g. Escape), which is surfaced as `DMenuError::Cancelled`
+    /// rather than an empty selection; any other non-zero status is a genuine
+    /// failure and is surfaced as `DMenuError::Failed` instead.
+    fn run(&self, input: String) -> Result<String, DMenuError> {
+        let mut child = Command::new(self.backend.program())
+            .args(self.to_command())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(DMenuError::Spawn)?;
+
+        let mut stdin = child.stdin.take().expect("stdin was piped");
+        let writer = thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+
+        let output = child.wait_with_output().map_err(DMenuError::Spawn)?;
+        writer.join().expect("stdin writer thread panicked");
+
+        if !output.status.success() {
+            return Err(match output.status.code() {
+                Some(1) => DMenuError::Cancelled,
+                _ => DMenuError::Failed(output.status),
+            });
+        }
+
+        String::from_utf8(output.stdout).map_err(DMenuError::Utf8)
     }
 
     /// Execute the dmenu struct as a command. Blocks the program till the user completes
@@ -220,82 +472,152 @@ impl<'a> DMenu<'a> {
     ///     println!("{}", item);
     /// }
     /// ```
-    pub fn execute<T: Display>(self, list: &Vec<T>) -> Result<&T, Box<dyn Error>> {
+    pub fn execute<T: Display>(self, list: &Vec<T>) -> Result<&T, DMenuError> {
         let mut map: HashMap<String, &T> = HashMap::new();
-        let mut list_string = String::from("");
+        let mut keys = Vec::new();
         for item in list {
             let key: String = format!("{}\n", item);
-            list_string.push_str(&key);
+            keys.push(key.clone());
             map.insert(key, item);
         }
 
-        let shell_output = Command::new("sh")
-            .args(&[
-                "-c",
-                &format!("echo -e '{}' | {}", list_string, self.to_command()),
-            ])
-            .output()?;
-
-        let chosen = String::from_utf8(shell_output.stdout)?;
+        let chosen = self.run(self.ordered_list_string(keys))?;
 
         match map.get(&chosen) {
             Some(found) => Ok(found),
-            None => Err(Box::new(ItemNotFoundError)),
+            None => Err(DMenuError::ItemNotFound),
         }
     }
 
     /// Like execute, but consumes the list to return an owned item after the user chooses.
-    pub fn execute_consume<T: Display>(self, list: Vec<T>) -> Result<T, Box<dyn Error>> {
+    pub fn execute_consume<T: Display>(self, list: Vec<T>) -> Result<T, DMenuError> {
         let mut map: HashMap<String, T> = HashMap::new();
-        let mut list_string = String::from("");
+        let mut keys = Vec::new();
         for item in list {
             let key: String = format!("{}\n", item);
-            list_string.push_str(&key);
+            keys.push(key.clone());
             map.insert(key, item);
         }
 
-        let shell_output = Command::new("sh")
-            .args(&[
-                "-c",
-                &format!("echo -e '{}' | {}", list_string, self.to_command()),
-            ])
-            .output()?;
-
-        let chosen = String::from_utf8(shell_output.stdout)?;
+        let chosen = self.run(self.ordered_list_string(keys))?;
 
         match map.remove(&chosen) {
             Some(found) => Ok(found),
-            None => Err(Box::new(ItemNotFoundError)),
+            None => Err(DMenuError::ItemNotFound),
         }
     }
 
-    /// Will launch the configured DMenu without any items and return the string typed by the user
-    pub fn execute_as_input(self) -> Result<String, Box<dyn Error>> {
-        let shell_output = Command::new("sh")
-            .arg("-c")
-            .arg(format!("echo -e '\n' | {}", self.to_command()))
-            .output()?;
-
-        let mut string = String::from_utf8(shell_output.stdout)?;
+    /// Will launch the configured DMenu without any items and return the string typed by the user.
+    ///
+    /// When combined with `.password()`, the returned `String` holds whatever secret the
+    /// user typed; it is not zeroized on drop, so callers handling sensitive input should
+    /// take care not to log, persist, or otherwise leak it.
+    pub fn execute_as_input(self) -> Result<String, DMenuError> {
+        let mut string = self.run(String::new())?;
         string.pop(); // remove newline
         Ok(string)
     }
+
+    /// Like execute, but for a dmenu build/fork that supports selecting more than one item
+    /// (each chosen entry printed on its own line of stdout).
+    ///
+    /// Returns an empty `Vec` rather than an error when the user confirms with no
+    /// selection, so callers can tell "chose nothing" apart from a spawn failure.
+    /// # Example
+    /// ```no_run
+    /// use dmenu_facade::*;
+    /// let items = vec!["Hello", "There", "Hope you", "Like my", "Docs :)"];
+    /// let chosen = DMenu::default()
+    ///                 .vertical_with_lines(4)
+    ///                 .execute_multi(&items);
+    /// if let Ok(items) = chosen {
+    ///     for item in items {
+    ///         println!("{}", item);
+    ///     }
+    /// }
+    /// ```
+    pub fn execute_multi<T: Display>(self, list: &Vec<T>) -> Result<Vec<&T>, DMenuError> {
+        let mut map: HashMap<String, &T> = HashMap::new();
+        let mut keys = Vec::new();
+        for item in list {
+            let key: String = format!("{}\n", item);
+            keys.push(key.clone());
+            map.insert(key, item);
+        }
+
+        let chosen = self.run(self.ordered_list_string(keys))?;
+
+        Ok(chosen
+            .split('\n')
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| map.get(&format!("{}\n", line)).copied())
+            .collect())
+    }
+
+    /// Like execute_multi, but consumes the list to return owned items after the user chooses.
+    pub fn execute_multi_consume<T: Display>(self, list: Vec<T>) -> Result<Vec<T>, DMenuError> {
+        let mut map: HashMap<String, T> = HashMap::new();
+        let mut keys = Vec::new();
+        for item in list {
+            let key: String = format!("{}\n", item);
+            keys.push(key.clone());
+            map.insert(key, item);
+        }
+
+        let chosen = self.run(self.ordered_list_string(keys))?;
+
+        Ok(chosen
+            .split('\n')
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| map.remove(&format!("{}\n", line)))
+            .collect())
+    }
 }
 
 /// A struct for containing a color string.
 #[derive(Clone, PartialEq, PartialOrd, Ord, Eq, Debug)]
 pub struct Color<'a>(pub &'a str);
 
-#[derive(Clone, PartialEq, PartialOrd, Ord, Eq, Debug)]
-pub struct ItemNotFoundError;
+/// Everything that can go wrong running a DMenu, distinguishing the user
+/// backing out (`Cancelled`) from a failure to spawn it (`Spawn`), from a
+/// genuine non-zero exit (`Failed`) and from an actual failure to round-trip a
+/// selection (`ItemNotFound`).
+#[derive(Debug)]
+pub enum DMenuError {
+    /// dmenu could not be spawned or its output could not be read.
+    Spawn(io::Error),
+    /// dmenu's stdout was not valid UTF-8.
+    Utf8(FromUtf8Error),
+    /// The user aborted the prompt (dmenu exited with status code 1).
+    Cancelled,
+    /// dmenu exited with a non-zero status other than the code it uses for
+    /// user cancellation, e.g. it could not open a display.
+    Failed(ExitStatus),
+    /// dmenu returned a selection that isn't present in the original item set.
+    ItemNotFound,
+}
 
-impl Display for ItemNotFoundError {
+impl Display for DMenuError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "The returned item by DMenu is not found in the original set"
-        )
+        match self {
+            DMenuError::Spawn(e) => write!(f, "failed to run dmenu: {}", e),
+            DMenuError::Utf8(e) => write!(f, "dmenu output was not valid UTF-8: {}", e),
+            DMenuError::Cancelled => write!(f, "the dmenu prompt was cancelled by the user"),
+            DMenuError::Failed(status) => write!(f, "dmenu exited with {}", status),
+            DMenuError::ItemNotFound => write!(
+                f,
+                "the returned item by DMenu is not found in the original set"
+            ),
+        }
     }
 }
 
-impl Error for ItemNotFoundError {}
+impl Error for DMenuError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DMenuError::Spawn(e) => Some(e),
+            DMenuError::Utf8(e) => Some(e),
+            DMenuError::Cancelled | DMenuError::Failed(_) | DMenuError::ItemNotFound => None,
+        }
+    }
+}
@@ -1,4 +1,11 @@
-use std::{collections::HashMap, error::Error, fmt::Display, process::Command};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
 
 pub fn main() -> Result<(), Box<dyn Error>> {
     let dmenu = DMenu::default()
@@ -91,49 +98,57 @@ impl<'a> DMenu<'a> {
         self
     }
 
-    fn to_command(&self) -> String {
-        let mut command = "dmenu".to_string();
+    fn to_command(&self) -> Vec<String> {
+        let mut args = Vec::new();
         if !self.on_top {
-            command.push_str(" -b");
+            args.push("-b".to_string());
         }
 
         if self.case_insensitive {
-            command.push_str(" -i");
+            args.push("-i".to_string());
         }
 
         if let Some(lines) = self.vertical_lines {
-            command.push_str(&format!(" -l {}", lines))
+            args.push("-l".to_string());
+            args.push(lines.to_string());
         };
 
         if let Some(monitor_index) = self.monitor {
-            command.push_str(&format!(" -m {}", monitor_index));
+            args.push("-m".to_string());
+            args.push(monitor_index.to_string());
         }
 
         if let Some(prompt) = &self.prompt {
-            command.push_str(&format!(" -p '{}'", prompt));
+            args.push("-p".to_string());
+            args.push(prompt.to_string());
         }
 
         if let Some(font) = &self.font {
-            command.push_str(&format!(" -fn '{}'", font));
+            args.push("-fn".to_string());
+            args.push(font.to_string());
         }
 
         if let Some(nb) = &self.normal_background_color {
-            command.push_str(&format!(" -nb '{}'", nb.0));
+            args.push("-nb".to_string());
+            args.push(nb.0.to_string());
         }
 
         if let Some(nf) = &self.normal_foreground_color {
-            command.push_str(&format!(" -nf '{}'", nf.0));
+            args.push("-nf".to_string());
+            args.push(nf.0.to_string());
         }
 
         if let Some(sb) = &self.selected_background_color {
-            command.push_str(&format!(" -sb '{}'", sb.0));
+            args.push("-sb".to_string());
+            args.push(sb.0.to_string());
         }
 
         if let Some(sf) = &self.selected_foreground_color {
-            command.push_str(&format!(" -sf '{}'", sf.0));
+            args.push("-sf".to_string());
+            args.push(sf.0.to_string());
         }
 
-        command
+        args
     }
 
     pub fn execute<T: Display>(self, list: &Vec<T>) -> Result<&T, Box<dyn Error>> {
@@ -145,16 +160,21 @@ impl<'a> DMenu<'a> {
             map.insert(key, item);
         }
 
-        println!("{}", self.to_command());
+        let mut child = Command::new("dmenu")
+            .args(self.to_command())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
 
-        let shell_output = Command::new("sh")
-            .args(&[
-                "-c",
-                &format!("echo -e '{}' | {}", list_string, self.to_command()),
-            ])
-            .output()?;
+        let mut stdin = child.stdin.take().expect("stdin was piped");
+        let writer = thread::spawn(move || {
+            let _ = stdin.write_all(list_string.as_bytes());
+        });
 
-        let chosen = String::from_utf8(shell_output.stdout)?;
+        let output = child.wait_with_output()?;
+        writer.join().expect("stdin writer thread panicked");
+
+        let chosen = String::from_utf8(output.stdout)?;
 
         match map.get(&chosen) {
             Some(found) => Ok(found),